@@ -0,0 +1,16 @@
+pub mod posts;
+pub mod routes;
+mod views;
+
+use std::io;
+use std::path::Path;
+
+use posts::PostList;
+
+/// Writes the RSS feed to `feed.xml` in `output_dir`. The `at_*` HTML
+/// pages are not written by this function.
+pub fn build(posts: &PostList, output_dir: &Path) -> io::Result<()> {
+    routes::write_feed(posts, output_dir)?;
+
+    Ok(())
+}
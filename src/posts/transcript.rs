@@ -1,4 +1,8 @@
-use anyhow::{bail, Result};
+use std::fs;
+use std::path::Path;
+
+use chumsky::prelude::*;
+use chumsky::extra;
 
 #[derive(Clone, Debug)]
 pub enum Transcript {
@@ -14,7 +18,17 @@ pub struct Panel {
 #[derive(Clone, Debug)]
 pub struct Line {
     pub speaker: Speaker,
-    pub text: String,
+    pub text: Vec<TextSpan>,
+}
+
+/// A run of transcript text, parsed from a small inline-markdown subset
+/// (`*italic*`, `**bold**`) so views and the `json` export don't have to
+/// deal in raw, unstyled strings.
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub enum TextSpan {
+    Text(String),
+    Italic(Vec<TextSpan>),
+    Bold(Vec<TextSpan>),
 }
 
 #[derive(Clone, Debug)]
@@ -24,6 +38,76 @@ pub enum Speaker {
     Character { name: String, uncommon: bool },
 }
 
+/// Severity of a [`Diagnostic`] produced while parsing a transcript.
+#[derive(Clone, Copy, Debug, PartialEq, Eq)]
+pub enum Level {
+    Warning,
+    Error,
+}
+
+/// A single problem found while parsing a transcript, with the 1-based
+/// source line it applies to so a build can point at the exact spot
+/// (`0` for problems with the file as a whole).
+#[derive(Clone, Debug)]
+pub struct Diagnostic {
+    pub line: usize,
+    pub level: Level,
+    pub message: String,
+}
+
+impl Diagnostic {
+    fn error(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            level: Level::Error,
+            message: message.into(),
+        }
+    }
+
+    fn warning(line: usize, message: impl Into<String>) -> Self {
+        Self {
+            line,
+            level: Level::Warning,
+            message: message.into(),
+        }
+    }
+
+    /// Prints the diagnostic, reusing the yellow-highlight style the
+    /// build output already uses for `?`/`!` warnings.
+    pub fn print(&self) {
+        let prefix = match self.level {
+            Level::Error => '!',
+            Level::Warning => '?',
+        };
+        println!("{prefix} \x1b[33mline {}: {}\x1b[0m", self.line, self.message);
+    }
+}
+
+/// A single non-empty, trimmed line of a transcript file, tagged with its
+/// 1-based line number so later diagnostics can point back at it.
+#[derive(Clone, Debug, PartialEq, Eq, Hash)]
+struct LineSpan {
+    line: usize,
+    text: String,
+}
+
+const COMMON_NAMES: &[&str] = &[
+    "garfildo",
+    "jono",
+    "lizo",
+    "odio",
+    "nermalo",
+    "arlino",
+    "hundo",
+    "televidilo",
+    "irma",
+    "muso",
+    "araneo",
+    "pesilo",
+    "patrino",
+    "patro",
+];
+
 impl Transcript {
     pub fn panels(&self) -> &[Panel] {
         match self {
@@ -45,6 +129,63 @@ impl Transcript {
             })
             .collect()
     }
+
+    /// Parses a transcript file, recovering from malformed lines so that a
+    /// single build reports every problem at once instead of aborting on
+    /// the first one.
+    ///
+    /// Returns the transcript if one could be built at all, alongside every
+    /// [`Diagnostic`] collected along the way (errors *and* warnings).
+    pub fn parse(file: &str) -> (Option<Self>, Vec<Diagnostic>) {
+        let tokens = tokenize(file);
+        let mut diagnostics = Vec::new();
+
+        if tokens.is_empty() {
+            diagnostics.push(Diagnostic::error(0, "Empty file"));
+            return (None, diagnostics);
+        }
+
+        let panels = parse_panels(&tokens, &mut diagnostics);
+
+        let transcript = match panels.len() {
+            3 => Some(Transcript::Normal(
+                panels.try_into().expect("panels should convert to array"),
+            )),
+            7 => Some(Transcript::Sunday(
+                panels.try_into().expect("panels should convert to array"),
+            )),
+            n => {
+                diagnostics.push(Diagnostic::error(
+                    0,
+                    format!("Must contain exactly 3 OR 7 panels, found {n}"),
+                ));
+                None
+            }
+        };
+
+        (transcript, diagnostics)
+    }
+
+    /// Reads a transcript file from disk and parses it, printing every
+    /// diagnostic collected along the way so a single build reports every
+    /// problem at once. This is the entry point post loading should use in
+    /// place of the old `TryFrom<String>`.
+    pub fn load(path: &Path) -> Option<Self> {
+        let file = match fs::read_to_string(path) {
+            Ok(file) => file,
+            Err(error) => {
+                Diagnostic::error(0, format!("failed to read `{}`: {error}", path.display()))
+                    .print();
+                return None;
+            }
+        };
+
+        let (transcript, diagnostics) = Self::parse(&file);
+        for diagnostic in &diagnostics {
+            diagnostic.print();
+        }
+        transcript
+    }
 }
 
 impl Speaker {
@@ -56,126 +197,264 @@ impl Speaker {
     }
 }
 
-impl TryFrom<String> for Transcript {
-    type Error = anyhow::Error;
-    fn try_from(file: String) -> Result<Self> {
-        let mut panels_lines = Vec::new();
-        let mut lines = Vec::new();
+fn tokenize(file: &str) -> Vec<LineSpan> {
+    file.lines()
+        .enumerate()
+        .map(|(i, text)| LineSpan {
+            line: i + 1,
+            text: text.trim().to_string(),
+        })
+        .filter(|line| !line.text.is_empty())
+        .collect()
+}
 
-        for file_line in file.lines() {
-            let file_line = file_line.trim();
-            if file_line.is_empty() {
-                continue;
+/// Splits the tokenized file into panels at `---` separators, parsing each
+/// panel's (speaker, text) line pairs and recovering from bad ones by
+/// skipping forward to the next `---` or next plausible speaker line.
+fn parse_panels(tokens: &[LineSpan], diagnostics: &mut Vec<Diagnostic>) -> Vec<Panel> {
+    tokens
+        .split(|token| token.text == "---")
+        .map(|panel_tokens| parse_panel(panel_tokens, diagnostics))
+        .collect()
+}
+
+fn parse_panel(tokens: &[LineSpan], diagnostics: &mut Vec<Diagnostic>) -> Panel {
+    let mut lines = Vec::new();
+    let mut i = 0;
+
+    while i < tokens.len() {
+        let speaker_token = &tokens[i];
+        let lowered = speaker_token.text.to_lowercase();
+
+        match speaker_parser().parse(lowered.as_str()).into_result() {
+            Ok(speaker) => {
+                check_common_name(&speaker, speaker_token.line, diagnostics);
+
+                let Some(text_token) = tokens.get(i + 1) else {
+                    diagnostics.push(Diagnostic::error(
+                        speaker_token.line,
+                        format!("expected text line after `{}`", speaker_token.text),
+                    ));
+                    break;
+                };
+
+                if is_plausible_speaker(&text_token.text) {
+                    diagnostics.push(Diagnostic::error(
+                        speaker_token.line,
+                        format!("expected text line after `{}`", speaker_token.text),
+                    ));
+                    i += 1;
+                    continue;
+                }
+
+                let text = parse_text_spans(&text_token.text);
+                lines.push(Line { speaker, text });
+                i += 2;
             }
-            if file_line == "---" {
-                panels_lines.push(lines);
-                lines = Vec::new();
-            } else {
-                lines.push(file_line);
+            Err(_) => {
+                diagnostics.push(Diagnostic::error(
+                    speaker_token.line,
+                    format!("Not a valid speaker `{}`", speaker_token.text),
+                ));
+                i += skip_to_recovery_point(&tokens[i + 1..]) + 1;
             }
         }
-        panels_lines.push(lines);
+    }
 
-        if panels_lines.is_empty() {
-            bail!("Empty file");
-        }
+    Panel { lines }
+}
 
-        let mut panels = Vec::new();
+/// Skips tokens until the next plausible speaker line, so a bad line
+/// doesn't take the rest of the panel down with it.
+fn skip_to_recovery_point(tokens: &[LineSpan]) -> usize {
+    tokens
+        .iter()
+        .position(|token| is_plausible_speaker(&token.text))
+        .unwrap_or(tokens.len())
+}
 
-        for lines in panels_lines {
-            panels.push(Panel::try_from(lines)?);
-        }
+fn is_plausible_speaker(text: &str) -> bool {
+    let lowered = text.to_lowercase();
+    speaker_parser().parse(lowered.as_str()).into_result().is_ok()
+}
 
-        let transcript = match panels.len() {
-            3 => Transcript::Normal(panels.try_into().expect("panels should convert to array")),
-            7 => Transcript::Sunday(panels.try_into().expect("panels should convert to array")),
-            _ => bail!("Must contain exactly 3 OR 7 panels"),
-        };
+fn check_common_name(speaker: &Speaker, line: usize, diagnostics: &mut Vec<Diagnostic>) {
+    let Speaker::Character { name, uncommon } = speaker else {
+        return;
+    };
+    let is_common = COMMON_NAMES.contains(&name.as_str());
 
-        Ok(transcript)
+    if *uncommon && is_common {
+        diagnostics.push(Diagnostic::warning(
+            line,
+            format!("`~{name}` is marked uncommon but is a common name"),
+        ));
+    } else if !*uncommon && !is_common {
+        diagnostics.push(Diagnostic::warning(
+            line,
+            format!("`{name}` is not a common name, consider marking it with `~`"),
+        ));
     }
 }
 
-impl TryFrom<Vec<&str>> for Panel {
-    type Error = anyhow::Error;
-    fn try_from(strings: Vec<&str>) -> Result<Self> {
-        let mut strings = strings.into_iter();
-        let mut lines = Vec::new();
+/// A chumsky parser recognising a single speaker line: either a bracket
+/// token (`[sono]`/`[skribo]`), or a name ending in `:` with an optional
+/// leading `~` marking it uncommon.
+fn speaker_parser<'a>() -> impl Parser<'a, &'a str, Speaker, extra::Err<Rich<'a, char>>> + Clone {
+    let bracket = choice((
+        just("[sono]").to(Speaker::Sound),
+        just("[skribo]").to(Speaker::Text),
+    ));
+
+    let name = just('~')
+        .or_not()
+        .then(
+            any()
+                .filter(|c: &char| *c != ':')
+                .repeated()
+                .at_least(1)
+                .collect::<String>(),
+        )
+        .then_ignore(just(':'))
+        .map(|(tilde, name): (Option<char>, String)| Speaker::Character {
+            name: name.to_lowercase(),
+            uncommon: tilde.is_some(),
+        });
+
+    bracket.or(name).then_ignore(end())
+}
+
+/// Parses a line of transcript text into a sequence of spans, supporting
+/// `*italic*` and `**bold**` emphasis (the `~` -> `♫` rule still applies to
+/// plain text). Modelled on GitHub-flavored inline parsing: scan the line
+/// into runs, track emphasis delimiters, and on a matching closing
+/// delimiter wrap the enclosed spans; unmatched delimiters fall back to
+/// literal characters.
+fn parse_text_spans(string: &str) -> Vec<TextSpan> {
+    let chars: Vec<char> = string.chars().collect();
+    let mut spans = Vec::new();
+    let mut literal = String::new();
+    let mut i = 0;
 
-        while let Some(string) = strings.next() {
-            let speaker = Speaker::try_from(string)?;
+    while i < chars.len() {
+        if chars[i] == '~' {
+            literal.push('♫');
+            i += 1;
+            continue;
+        }
+
+        if chars[i] == '*' {
+            let run_len = if delimiter_run_len(&chars[i..]) >= 2 { 2 } else { 1 };
+
+            if let Some(end) = find_closing_delimiter(&chars[i + run_len..], run_len) {
+                if !literal.is_empty() {
+                    spans.push(TextSpan::Text(std::mem::take(&mut literal)));
+                }
 
-            let Some(text) = strings.next() else {
-                bail!("expected text line after `{}`", string);
-            };
-            let text = format_transcript_text(text);
+                let inner: String = chars[i + run_len..i + run_len + end].iter().collect();
+                let inner = parse_text_spans(&inner);
+                spans.push(if run_len == 2 {
+                    TextSpan::Bold(inner)
+                } else {
+                    TextSpan::Italic(inner)
+                });
 
-            lines.push(Line { speaker, text });
+                i += run_len + end + run_len;
+                continue;
+            }
         }
 
-        Ok(Panel { lines })
+        literal.push(chars[i]);
+        i += 1;
     }
+
+    if !literal.is_empty() {
+        spans.push(TextSpan::Text(literal));
+    }
+
+    spans
 }
 
-impl TryFrom<&str> for Speaker {
-    type Error = anyhow::Error;
-    fn try_from(string: &str) -> Result<Self> {
-        if !string.ends_with(':') {
-            return Ok(match string.to_lowercase().as_str() {
-                "[sono]" => Self::Sound,
-                "[skribo]" => Self::Text,
-                _ => bail!("Not a valid speaker `{}`", string),
-            });
-        }
+/// Finds the index (within `chars`) of a closing delimiter run whose full
+/// length is exactly `run_len`, returning `None` if the opening delimiter
+/// is unmatched. A run of the wrong length (e.g. the first `*` of a `**`
+/// run when looking for a single-`*` close) is skipped over in its
+/// entirety rather than partially consumed, so it stays eligible to close
+/// a *different* emphasis run later in the text.
+fn find_closing_delimiter(chars: &[char], run_len: usize) -> Option<usize> {
+    let mut i = 0;
 
-        let name = remove_last_char(string).to_lowercase();
-        let uncommon = name.starts_with('~');
-
-        const COMMON_NAMES: &[&str] = &[
-            "garfildo",
-            "jono",
-            "lizo",
-            "odio",
-            "nermalo",
-            "arlino",
-            "hundo",
-            "televidilo",
-            "irma",
-            "muso",
-            "araneo",
-            "pesilo",
-            "patrino",
-            "patro",
-        ];
-
-        let name = if uncommon {
-            let name = remove_first_char(&name);
-            if COMMON_NAMES.contains(&name) {
-                println!("! ~\x1b[33m{}\x1b[0m", name.to_uppercase());
-            }
-            name.to_string()
-        } else {
-            if !COMMON_NAMES.contains(&name.as_str()) {
-                println!("? \x1b[33m{}\x1b[0m", name.to_uppercase());
+    while i < chars.len() {
+        if chars[i] == '*' {
+            let closing_len = delimiter_run_len(&chars[i..]);
+
+            if closing_len == run_len {
+                return Some(i);
             }
-            name
-        };
 
-        Ok(Self::Character { name, uncommon })
+            i += closing_len;
+            continue;
+        }
+
+        i += 1;
     }
-}
 
-fn format_transcript_text(string: &str) -> String {
-    string.replace('~', "♫")
+    None
 }
 
-fn remove_last_char(string: &str) -> &str {
-    let mut chars = string.chars();
-    chars.next_back();
-    chars.as_str()
+/// Counts the number of consecutive `*` characters at the start of `chars`.
+fn delimiter_run_len(chars: &[char]) -> usize {
+    chars.iter().take_while(|c| **c == '*').count()
 }
-fn remove_first_char(string: &str) -> &str {
-    let mut chars = string.chars();
-    chars.next();
-    chars.as_str()
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn bold_and_italic_spans_parse() {
+        assert_eq!(
+            parse_text_spans("*italic*"),
+            vec![TextSpan::Italic(vec![TextSpan::Text("italic".to_string())])],
+        );
+        assert_eq!(
+            parse_text_spans("**bold**"),
+            vec![TextSpan::Bold(vec![TextSpan::Text("bold".to_string())])],
+        );
+        assert_eq!(
+            parse_text_spans("a~b"),
+            vec![TextSpan::Text("a♫b".to_string())],
+        );
+    }
+
+    #[test]
+    fn a_bold_run_does_not_close_an_unrelated_italic_run() {
+        // The first `*` of the `**` run must not be consumed as the italic's
+        // closing delimiter, dropping the second `*` on the floor.
+        assert_eq!(
+            parse_text_spans("*foo**bar*"),
+            vec![TextSpan::Italic(vec![TextSpan::Text(
+                "foo**bar".to_string()
+            )])],
+        );
+    }
+
+    #[test]
+    fn unmatched_delimiters_fall_back_to_literal_characters() {
+        assert_eq!(
+            parse_text_spans("*oops"),
+            vec![TextSpan::Text("*oops".to_string())],
+        );
+    }
+
+    #[test]
+    fn recovers_from_a_malformed_speaker_line() {
+        let file = "jono:\nHello\n---\nnot a speaker\nnermalo:\nHi\n---\nodio:\nBye";
+        let (transcript, diagnostics) = Transcript::parse(file);
+
+        assert!(diagnostics.iter().any(|d| d.level == Level::Error));
+
+        let transcript = transcript.expect("panel count is still valid despite the bad line");
+        assert_eq!(transcript.panels()[1].lines.len(), 1);
+    }
 }
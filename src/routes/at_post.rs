@@ -0,0 +1,50 @@
+use ibex::prelude::*;
+
+use crate::posts::{Post, PostList, TextSpan};
+use crate::views::use_base;
+
+pub fn at_post(posts: &PostList, post: &Post) -> Document {
+    document! { [lang="eo"] @use_base [
+        post.title.clone(),
+        None,
+        posts,
+    ] {
+        h2 { [&post.title] }
+        img [
+            alt=&post.title,
+            src=assets_url!(format!("posts/{}/esperanto.png", &post.index)),
+        ]/
+
+        [:if let Some(transcript) = &post.transcript {
+            div ."transcript" {
+                [:for panel in transcript.panels() {
+                    div ."panel" {
+                        [:for line in &panel.lines {
+                            p ."line" {
+                                @render_spans [&line.text]
+                            }
+                        }]
+                    }
+                }]
+            }
+        }]
+    }}
+}
+
+/// Renders a line's parsed spans into `<i>`/`<b>` nodes, recursing for
+/// nested emphasis (e.g. `**bold *and italic* text**`).
+fn render_spans(spans: &[TextSpan]) -> View {
+    view! {
+        [:for span in spans {
+            @render_span [span]
+        }]
+    }
+}
+
+fn render_span(span: &TextSpan) -> View {
+    match span {
+        TextSpan::Text(text) => view! { [text] },
+        TextSpan::Italic(spans) => view! { i { @render_spans [spans] } },
+        TextSpan::Bold(spans) => view! { b { @render_spans [spans] } },
+    }
+}
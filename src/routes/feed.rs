@@ -0,0 +1,72 @@
+use std::fs;
+use std::io;
+use std::path::Path;
+
+use crate::posts::PostList;
+
+/// Writes the RSS feed to `feed.xml` at the site root. This is the build's
+/// entry point for the feed: call it alongside the other `at_*` page
+/// writes when generating the site.
+pub fn write_feed(posts: &PostList, output_dir: &Path) -> io::Result<()> {
+    fs::write(output_dir.join("feed.xml"), at_feed(posts))
+}
+
+/// Builds an RSS 2.0 feed of every post, newest first, so feed readers and
+/// aggregators can track new Esperanto translations without polling the
+/// site. Written to `feed.xml` at the site root by [`write_feed`].
+pub fn at_feed(posts: &PostList) -> String {
+    let mut items = String::new();
+
+    for post in posts.into_iter().rev() {
+        let post = post.get();
+        let link = url!(post.index());
+        let pub_date = post.date.format("%a, %d %b %Y 00:00:00 GMT");
+        let image = assets_url!(format!("posts/{}/esperanto.png", &post.index));
+
+        items.push_str(&format!(
+            "    <item>
+      <title>{}</title>
+      <link>{link}</link>
+      <guid>{link}</guid>
+      <pubDate>{pub_date}</pubDate>
+      <description>{}</description>
+    </item>
+",
+            escape_xml(&post.title),
+            escape_xml(&format!("<img src=\"{image}\" />")),
+        ));
+    }
+
+    format!(
+        "<?xml version=\"1.0\" encoding=\"UTF-8\"?>
+<rss version=\"2.0\">
+  <channel>
+    <title>Garfeo</title>
+    <link>{}</link>
+    <description>Esperanto translations of Garfield comics</description>
+{items}  </channel>
+</rss>
+",
+        url!(""),
+    )
+}
+
+fn escape_xml(string: &str) -> String {
+    string
+        .replace('&', "&amp;")
+        .replace('<', "&lt;")
+        .replace('>', "&gt;")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn escapes_xml_special_characters() {
+        assert_eq!(
+            escape_xml("Garfildo & Jono <3"),
+            "Garfildo &amp; Jono &lt;3",
+        );
+    }
+}
@@ -0,0 +1,81 @@
+use crate::posts::{Post, PostList, Speaker, TextSpan, Transcript};
+
+/// Machine-readable JSON export of every post, consumed by tooling that
+/// wants structured data (including parsed transcript spans) instead of
+/// scraping HTML.
+pub fn at_json(posts: &PostList) -> String {
+    let posts: Vec<String> = posts.into_iter().map(|post| post_json(post.get())).collect();
+
+    format!("[\n{}\n]\n", posts.join(",\n"))
+}
+
+fn post_json(post: &Post) -> String {
+    let transcript = post
+        .transcript
+        .as_ref()
+        .map(transcript_json)
+        .unwrap_or_else(|| "null".to_string());
+
+    format!(
+        "  {{ \"index\": {}, \"title\": {}, \"transcript\": {transcript} }}",
+        post.index,
+        escape_json(&post.title),
+    )
+}
+
+fn transcript_json(transcript: &Transcript) -> String {
+    let panels: Vec<String> = transcript
+        .panels()
+        .iter()
+        .map(|panel| {
+            let lines: Vec<String> = panel
+                .lines
+                .iter()
+                .map(|line| {
+                    format!(
+                        "{{ \"speaker\": {}, \"text\": {} }}",
+                        speaker_json(&line.speaker),
+                        spans_json(&line.text),
+                    )
+                })
+                .collect();
+            format!("[{}]", lines.join(", "))
+        })
+        .collect();
+
+    format!("[{}]", panels.join(", "))
+}
+
+fn speaker_json(speaker: &Speaker) -> String {
+    match speaker {
+        Speaker::Sound => "\"sound\"".to_string(),
+        Speaker::Text => "\"text\"".to_string(),
+        Speaker::Character { name, uncommon } => {
+            format!("{{ \"name\": {}, \"uncommon\": {uncommon} }}", escape_json(name))
+        }
+    }
+}
+
+fn spans_json(spans: &[TextSpan]) -> String {
+    let spans: Vec<String> = spans.iter().map(span_json).collect();
+    format!("[{}]", spans.join(", "))
+}
+
+fn span_json(span: &TextSpan) -> String {
+    match span {
+        TextSpan::Text(text) => format!("{{ \"kind\": \"text\", \"value\": {} }}", escape_json(text)),
+        TextSpan::Italic(spans) => {
+            format!("{{ \"kind\": \"italic\", \"value\": {} }}", spans_json(spans))
+        }
+        TextSpan::Bold(spans) => {
+            format!("{{ \"kind\": \"bold\", \"value\": {} }}", spans_json(spans))
+        }
+    }
+}
+
+fn escape_json(string: &str) -> String {
+    format!(
+        "\"{}\"",
+        string.replace('\\', "\\\\").replace('"', "\\\"")
+    )
+}
@@ -1,15 +1,17 @@
 mod at_about;
 mod at_post;
+mod feed;
 mod json;
 
 use ibex::prelude::*;
-use std::collections::HashSet;
+use std::collections::{HashMap, HashSet};
 
-use crate::posts::{Post, PostList};
+use crate::posts::{Post, PostList, Speaker, Transcript};
 use crate::views::{icons, list_item, post_special_icon, sentence_case, use_base};
 
 pub use at_about::*;
 pub use at_post::*;
+pub use feed::*;
 pub use json::*;
 
 pub fn at_index(posts: &PostList) -> Document {
@@ -86,6 +88,220 @@ fn posts_names(posts: &PostList) -> [Vec<(String, bool)>; 2] {
     [common, uncommon]
 }
 
+fn lines_percent<F>(posts: &PostList, predicate: F) -> usize
+where
+    F: Fn(&Speaker) -> bool,
+{
+    let mut total = 0;
+    let mut matching = 0;
+
+    for post in posts.iter() {
+        let Some(transcript) = &post.get().transcript else {
+            continue;
+        };
+        for panel in transcript.panels() {
+            for line in &panel.lines {
+                total += 1;
+                if predicate(&line.speaker) {
+                    matching += 1;
+                }
+            }
+        }
+    }
+
+    if total == 0 {
+        0
+    } else {
+        matching * 100 / total
+    }
+}
+
+/// Total lines spoken and posts appeared in, for a single named character.
+struct SpeakerLineCount {
+    name: String,
+    lines: usize,
+    posts: usize,
+}
+
+/// How many lines each named character speaks, and in how many posts they
+/// appear, ranked by line count.
+fn speaker_line_counts(posts: &PostList) -> Vec<SpeakerLineCount> {
+    count_speaker_lines(posts.iter().filter_map(|post| {
+        let post = post.get();
+        post.transcript.as_ref().map(|transcript| (post.index, transcript))
+    }))
+}
+
+/// Core aggregation behind [`speaker_line_counts`], decoupled from
+/// `PostList` so it can be exercised directly with hand-built transcripts.
+fn count_speaker_lines<'a>(
+    entries: impl Iterator<Item = (u32, &'a Transcript)>,
+) -> Vec<SpeakerLineCount> {
+    let mut counts: HashMap<String, (usize, HashSet<u32>)> = HashMap::new();
+
+    for (index, transcript) in entries {
+        for panel in transcript.panels() {
+            for line in &panel.lines {
+                let Some((name, _)) = line.speaker.character() else {
+                    continue;
+                };
+
+                let entry = counts
+                    .entry(name.to_string())
+                    .or_insert_with(|| (0, HashSet::new()));
+                entry.0 += 1;
+                entry.1.insert(index);
+            }
+        }
+    }
+
+    let mut counts: Vec<_> = counts
+        .into_iter()
+        .map(|(name, (lines, posts))| SpeakerLineCount {
+            name,
+            lines,
+            posts: posts.len(),
+        })
+        .collect();
+    counts.sort_by(|a, b| b.lines.cmp(&a.lines));
+    counts
+}
+
+/// How often two named characters appear together in the same post,
+/// ranked by how often they share one.
+fn speaker_cooccurrence(posts: &PostList) -> Vec<(String, String, usize)> {
+    count_cooccurrences(
+        posts
+            .iter()
+            .filter_map(|post| post.get().transcript.clone())
+            .collect::<Vec<_>>()
+            .iter(),
+    )
+}
+
+/// Core aggregation behind [`speaker_cooccurrence`], decoupled from
+/// `PostList` so it can be exercised directly with hand-built transcripts.
+fn count_cooccurrences<'a>(
+    transcripts: impl Iterator<Item = &'a Transcript>,
+) -> Vec<(String, String, usize)> {
+    let mut counts: HashMap<(String, String), usize> = HashMap::new();
+
+    for transcript in transcripts {
+        let mut names: Vec<_> = transcript
+            .names()
+            .into_iter()
+            .map(|(name, _)| name)
+            .collect();
+        names.sort();
+        names.dedup();
+
+        for (i, a) in names.iter().enumerate() {
+            for b in &names[i + 1..] {
+                *counts.entry((a.clone(), b.clone())).or_insert(0) += 1;
+            }
+        }
+    }
+
+    let mut pairs: Vec<_> = counts
+        .into_iter()
+        .map(|((a, b), count)| (a, b, count))
+        .collect();
+    pairs.sort_by(|a, b| b.2.cmp(&a.2));
+    pairs
+}
+
+/// Click a `<th>` to sort its table's rows by that column (numeric if
+/// every cell parses as a number, lexical otherwise); click again to
+/// reverse. There's no existing JS hook in the repo to reuse, so this is
+/// a small self-contained script.
+const SORT_TABLE_SCRIPT: &str = r#"
+function sortTableByColumn(header) {
+    var table = header.closest('table');
+    var headerRow = header.parentNode;
+    var columnIndex = Array.prototype.indexOf.call(headerRow.children, header);
+    var body = headerRow.parentNode;
+    var rows = Array.prototype.filter.call(
+        table.rows,
+        function (row) { return row.parentNode === body && row !== headerRow; }
+    );
+    var numeric = rows.every(function (row) {
+        return !isNaN(parseFloat(row.children[columnIndex].textContent.trim()));
+    });
+
+    var ascending = header.getAttribute('data-sort-dir') !== 'asc';
+    rows.sort(function (a, b) {
+        var x = a.children[columnIndex].textContent.trim();
+        var y = b.children[columnIndex].textContent.trim();
+        var cmp = numeric ? parseFloat(x) - parseFloat(y) : x.localeCompare(y);
+        return ascending ? cmp : -cmp;
+    });
+
+    Array.prototype.forEach.call(headerRow.children, function (th) {
+        th.removeAttribute('data-sort-dir');
+    });
+    header.setAttribute('data-sort-dir', ascending ? 'asc' : 'desc');
+    rows.forEach(function (row) { body.appendChild(row); });
+}
+"#;
+
+pub fn at_stats(posts: &PostList) -> Document {
+    document! { [lang="eo"] @use_base [
+        "Statistikoj",
+        None,
+        posts,
+    ] {
+        br/
+        div ."big-list" {
+            div ."stats" {
+                table {
+                    [:where let character_percent = lines_percent(posts, |speaker| speaker.character().is_some()); {
+                        tr { td/ td { b { [character_percent] "%" } } td { "Parolaj linioj" } }
+                    }]
+                    [:where let sound_percent = lines_percent(posts, |speaker| matches!(speaker, Speaker::Sound)); {
+                        tr { td/ td { b { [sound_percent] "%" } } td { "Sonaj linioj" } }
+                    }]
+                    [:where let text_percent = lines_percent(posts, |speaker| matches!(speaker, Speaker::Text)); {
+                        tr { td/ td { b { [text_percent] "%" } } td { "Tekstaj linioj" } }
+                    }]
+                }
+            }
+            div ."names" {
+                table ."sortable" {
+                    tr {
+                        th [onclick="sortTableByColumn(this)"] { "Nomo" }
+                        th [onclick="sortTableByColumn(this)"] { "Linioj" }
+                        th [onclick="sortTableByColumn(this)"] { "Bildstrioj" }
+                    }
+                    [:for count in speaker_line_counts(posts) {
+                        tr {
+                            td { i { [sentence_case(&count.name, true)] } }
+                            td { [count.lines] }
+                            td { [count.posts] }
+                        }
+                    }]
+                }
+            }
+            div ."names" {
+                table ."sortable" {
+                    tr {
+                        th [onclick="sortTableByColumn(this)"] { "Rolulo" }
+                        th [onclick="sortTableByColumn(this)"] { "Rolulo" }
+                        th [onclick="sortTableByColumn(this)"] { "Kune" }
+                    }
+                    [:for (a, b, count) in speaker_cooccurrence(posts) {
+                        tr {
+                            td { i { [sentence_case(&a, true)] } }
+                            td { i { [sentence_case(&b, true)] } }
+                            td { [count] }
+                        }
+                    }]
+                }
+            }
+        }
+        script { [SORT_TABLE_SCRIPT] }
+    }}
+}
+
 pub fn at_list(posts: &PostList) -> Document {
     document! { [lang="eo"] @use_base [
         "Alia listo",
@@ -200,3 +416,58 @@ pub fn at_grid(posts: &PostList) -> Document {
         }
     } }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::posts::{Panel, Line};
+
+    fn character(name: &str) -> Speaker {
+        Speaker::Character {
+            name: name.to_string(),
+            uncommon: false,
+        }
+    }
+
+    fn speaking(speaker: Speaker) -> Line {
+        Line { speaker, text: Vec::new() }
+    }
+
+    #[test]
+    fn counts_lines_and_posts_per_speaker() {
+        let transcript = Transcript::Normal([
+            Panel {
+                lines: vec![speaking(character("jono")), speaking(character("lizo"))],
+            },
+            Panel {
+                lines: vec![speaking(character("jono"))],
+            },
+            Panel { lines: Vec::new() },
+        ]);
+
+        let counts = count_speaker_lines([(1, &transcript), (2, &transcript)].into_iter());
+
+        let jono = counts.iter().find(|count| count.name == "jono").unwrap();
+        assert_eq!(jono.lines, 4);
+        assert_eq!(jono.posts, 2);
+
+        let lizo = counts.iter().find(|count| count.name == "lizo").unwrap();
+        assert_eq!(lizo.lines, 2);
+        assert_eq!(lizo.posts, 2);
+    }
+
+    #[test]
+    fn counts_cooccurrences_of_characters_sharing_a_post() {
+        let transcript = Transcript::Normal([
+            Panel {
+                lines: vec![speaking(character("jono")), speaking(character("lizo"))],
+            },
+            Panel { lines: Vec::new() },
+            Panel { lines: Vec::new() },
+        ]);
+
+        let pairs = count_cooccurrences(std::iter::once(&transcript));
+
+        assert_eq!(pairs, vec![("jono".to_string(), "lizo".to_string(), 1)]);
+    }
+}